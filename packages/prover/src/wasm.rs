@@ -1,142 +1,542 @@
+mod error;
+
 use byteorder::{LittleEndian, ReadBytesExt};
 use console_error_panic_hook;
+use error::SpartanError;
 use ff::PrimeField;
 use libspartan::{Assignment, Instance, NIZKGens, NIZK};
 use merlin::Transcript;
-use secpq_curves::group::Group;
-use std::io::{Error, Read};
+use secpq_curves::group::{Group, GroupEncoding};
+use std::io::Read;
 use wasm_bindgen::prelude::*;
 
 pub type G1 = secpq_curves::secq256k1::Point;
 pub type F1 = <G1 as Group>::Scalar;
 
+// The secq256k1 scalar field modulus (the secp256k1 base field prime
+// `0xffffffff…fffffc2f`, little-endian), as circom's `.wtns` format encodes
+// it in its header. A witness generated for a different curve decodes fine
+// but produces a garbage proof, so this is checked rather than assumed.
+const SECQ256K1_FIELD_MODULUS: [u8; 32] = [
+    0x2f, 0xfc, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
 #[wasm_bindgen]
 pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
-#[wasm_bindgen]
-pub fn prove(circuit: &[u8], vars: &[u8], public_inputs: &[u8]) -> Result<Vec<u8>, JsValue> {
-    let witness = load_witness_from_bin_reader::<F1, _>(vars).unwrap();
-    let witness_bytes = witness
-        .iter()
-        .map(|w| w.to_repr())
-        .collect::<Vec<[u8; 32]>>();
+// Builds the Fiat-Shamir transcript a proof is bound to: the caller's
+// `domain_label` keeps proofs from two unrelated applications of this crate
+// from being replayable against one another, and absorbing the circuit's
+// dimensions plus its serialized bytes binds the proof to that specific
+// circuit rather than any other of the same domain.
+fn circuit_transcript(
+    domain_label: &[u8],
+    circuit_bytes: &[u8],
+    num_cons: usize,
+    num_vars: usize,
+    num_inputs: usize,
+) -> Transcript {
+    let mut transcript = Transcript::new(domain_label);
+    transcript.append_message(b"num_cons", &(num_cons as u64).to_le_bytes());
+    transcript.append_message(b"num_vars", &(num_vars as u64).to_le_bytes());
+    transcript.append_message(b"num_inputs", &(num_inputs as u64).to_le_bytes());
+    transcript.append_message(b"circuit", circuit_bytes);
+    transcript
+}
 
-    let assignment = Assignment::new(&witness_bytes).unwrap();
-    let circuit: Instance = bincode::deserialize(&circuit).unwrap();
+#[wasm_bindgen]
+pub fn prove(
+    circuit: &[u8],
+    vars: &[u8],
+    public_inputs: &[u8],
+    domain_label: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let instance: Instance =
+        bincode::deserialize(&circuit).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
 
-    let num_cons = circuit.inst.get_num_cons();
-    let num_vars = circuit.inst.get_num_vars();
-    let num_inputs = circuit.inst.get_num_inputs();
+    let num_cons = instance.inst.get_num_cons();
+    let num_vars = instance.inst.get_num_vars();
+    let num_inputs = instance.inst.get_num_inputs();
 
     // produce public parameters
     let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
 
-    let mut input = [[0u8; 32]];
-    for i in 0..num_inputs {
-        input[i] = public_inputs[(i * 32)..((i + 1) * 32)].try_into().unwrap();
+    // circom's witness vector is laid out wire-id order — [constant 1,
+    // public outputs, public inputs, private vars...] — while
+    // `r1cs_wire_to_col` packs only the private vars into z's var columns
+    // (0..num_vars); the constant and public wires get their own slots
+    // elsewhere in z. Drop that `1 + num_inputs`-element prefix so
+    // `Assignment::new` sees exactly the private-var columns it expects.
+    let witness = load_witness_from_bin_reader::<F1, _>(vars)?;
+    if witness.len() != 1 + num_inputs + num_vars {
+        return Err(SpartanError::InvalidInput(format!(
+            "expected a witness of length {} (1 constant + {} inputs + {} vars), got {}",
+            1 + num_inputs + num_vars,
+            num_inputs,
+            num_vars,
+            witness.len()
+        ))
+        .into());
     }
-    let input = Assignment::new(&input).unwrap();
+    let witness_bytes = witness[(1 + num_inputs)..]
+        .iter()
+        .map(|w| w.to_repr())
+        .collect::<Vec<[u8; 32]>>();
+
+    let assignment = Assignment::new(&witness_bytes)
+        .map_err(|e| SpartanError::FieldDecode(format!("invalid witness assignment: {:?}", e)))?;
+
+    if public_inputs.len() != num_inputs * 32 {
+        return Err(SpartanError::InvalidInput(format!(
+            "expected {} bytes of public input for {} inputs, got {}",
+            num_inputs * 32,
+            num_inputs,
+            public_inputs.len()
+        ))
+        .into());
+    }
+    let input: Vec<[u8; 32]> = (0..num_inputs)
+        .map(|i| public_inputs[(i * 32)..((i + 1) * 32)].try_into().unwrap())
+        .collect();
+    let input = Assignment::new(&input).map_err(|e| {
+        SpartanError::FieldDecode(format!("invalid public input assignment: {:?}", e))
+    })?;
 
-    let mut prover_transcript = Transcript::new(b"nizk_example");
+    let mut prover_transcript =
+        circuit_transcript(domain_label, circuit, num_cons, num_vars, num_inputs);
 
     // produce a proof of satisfiability
     let proof = NIZK::prove(
-        &circuit,
+        &instance,
         assignment.clone(),
         &input,
         &gens,
         &mut prover_transcript,
     );
 
-    Ok(bincode::serialize(&proof).unwrap())
+    bincode::serialize(&proof).map_err(|e| SpartanError::Deserialize(e.to_string()).into())
 }
 
 #[wasm_bindgen]
-pub fn verify(circuit: &[u8], proof: &[u8], public_input: &[u8]) -> Result<bool, JsValue> {
-    let circuit: Instance = bincode::deserialize(&circuit).unwrap();
-    let proof: NIZK = bincode::deserialize(&proof).unwrap();
+pub fn verify(
+    circuit: &[u8],
+    proof: &[u8],
+    public_input: &[u8],
+    domain_label: &[u8],
+) -> Result<bool, JsValue> {
+    let instance: Instance =
+        bincode::deserialize(&circuit).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
+    let proof: NIZK =
+        bincode::deserialize(&proof).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
 
-    let num_cons = circuit.inst.get_num_cons();
-    let num_vars = circuit.inst.get_num_vars();
-    let num_inputs = circuit.inst.get_num_inputs();
+    let num_cons = instance.inst.get_num_cons();
+    let num_vars = instance.inst.get_num_vars();
+    let num_inputs = instance.inst.get_num_inputs();
 
     // produce public parameters
     let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
 
-    let mut inputs = [[0u8; 32]];
-    for i in 0..num_inputs {
-        inputs[i] = public_input[(i * 32)..((i + 1) * 32)].try_into().unwrap();
+    if public_input.len() != num_inputs * 32 {
+        return Err(SpartanError::InvalidInput(format!(
+            "expected {} bytes of public input for {} inputs, got {}",
+            num_inputs * 32,
+            num_inputs,
+            public_input.len()
+        ))
+        .into());
     }
+    let inputs: Vec<[u8; 32]> = (0..num_inputs)
+        .map(|i| public_input[(i * 32)..((i + 1) * 32)].try_into().unwrap())
+        .collect();
 
-    let inputs = Assignment::new(&inputs).unwrap();
+    let inputs = Assignment::new(&inputs).map_err(|e| {
+        SpartanError::FieldDecode(format!("invalid public input assignment: {:?}", e))
+    })?;
 
-    let mut verifier_transcript = Transcript::new(b"nizk_example");
+    let mut verifier_transcript =
+        circuit_transcript(domain_label, circuit, num_cons, num_vars, num_inputs);
 
     let verified = proof
-        .verify(&circuit, &inputs, &mut verifier_transcript, &gens)
+        .verify(&instance, &inputs, &mut verifier_transcript, &gens)
         .is_ok();
 
     Ok(verified)
 }
 
 // Copied from Nova Scotia
-pub fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Fr, Error> {
+pub fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Fr, SpartanError> {
     let mut repr = Fr::zero().to_repr();
     for digit in repr.as_mut().iter_mut() {
         // TODO: may need to reverse order?
         *digit = reader.read_u8()?;
     }
-    let fr = Fr::from_repr(repr).unwrap();
-    Ok(fr)
+    Option::from(Fr::from_repr(repr))
+        .ok_or_else(|| SpartanError::FieldDecode("value is not a valid field element".into()))
+}
+
+// circom .r1cs binary format: magic "r1cs", u32 version, u32 section count,
+// then sections each prefixed by a u32 type and u64 byte length. We only
+// care about the header (type 1) and constraints (type 2) sections; any
+// other section (wire-to-label map, custom gates, ...) is skipped.
+const R1CS_HEADER_SECTION: u32 = 1;
+const R1CS_CONSTRAINTS_SECTION: u32 = 2;
+
+pub struct R1csHeader {
+    pub field_size: u32,
+    pub num_wires: u32,
+    pub num_pub_out: u32,
+    pub num_pub_in: u32,
+    pub num_prv_in: u32,
+    pub num_labels: u64,
+    pub num_constraints: u32,
+}
+
+fn read_r1cs_header<R: Read>(mut reader: R) -> Result<R1csHeader, SpartanError> {
+    let field_size = reader.read_u32::<LittleEndian>()?;
+    if field_size != 32 {
+        return Err(SpartanError::FieldDecode(format!(
+            "unexpected field byte size {}",
+            field_size
+        )));
+    }
+    let mut prime = vec![0u8; field_size as usize];
+    reader.read_exact(&mut prime)?;
+    let num_wires = reader.read_u32::<LittleEndian>()?;
+    let num_pub_out = reader.read_u32::<LittleEndian>()?;
+    let num_pub_in = reader.read_u32::<LittleEndian>()?;
+    let num_prv_in = reader.read_u32::<LittleEndian>()?;
+    let num_labels = reader.read_u64::<LittleEndian>()?;
+    let num_constraints = reader.read_u32::<LittleEndian>()?;
+    Ok(R1csHeader {
+        field_size,
+        num_wires,
+        num_pub_out,
+        num_pub_in,
+        num_prv_in,
+        num_labels,
+        num_constraints,
+    })
+}
+
+// Reads a single linear combination: a u32 term count followed by that many
+// (u32 wireId, field-element coefficient) pairs.
+fn read_lc<R: Read>(mut reader: R, field_size: u32) -> Result<Vec<(u32, [u8; 32])>, SpartanError> {
+    let num_terms = reader.read_u32::<LittleEndian>()?;
+    let mut terms = Vec::with_capacity(num_terms as usize);
+    for _ in 0..num_terms {
+        let wire_id = reader.read_u32::<LittleEndian>()?;
+        let mut coeff = [0u8; 32];
+        reader.read_exact(&mut coeff[..field_size as usize])?;
+        terms.push((wire_id, coeff));
+    }
+    Ok(terms)
+}
+
+// Remaps a circom wire id onto the column index Spartan's z = (vars, 1, inputs)
+// layout expects: the constant wire (0) lands just past the vars block, the
+// public in/out wires follow it, and every other wire is a private var.
+fn r1cs_wire_to_col(wire_id: u32, num_vars: usize, num_inputs: usize) -> usize {
+    if wire_id == 0 {
+        num_vars
+    } else if (wire_id as usize) <= num_inputs {
+        num_vars + wire_id as usize
+    } else {
+        wire_id as usize - 1 - num_inputs
+    }
+}
+
+/// Parses a circom `.r1cs` binary file and builds the `Instance` libspartan
+/// needs directly, without an offline bincode-conversion step.
+pub fn load_r1cs_from_bin_reader<R: Read>(mut reader: R) -> Result<Instance, SpartanError> {
+    let mut r1cs_header = [0u8; 4];
+    reader.read_exact(&mut r1cs_header)?;
+    if r1cs_header != [0x72, 0x31, 0x63, 0x73] {
+        return Err(SpartanError::InvalidHeader);
+    }
+    let _version = reader.read_u32::<LittleEndian>()?;
+    let num_sections = reader.read_u32::<LittleEndian>()?;
+
+    let mut header: Option<R1csHeader> = None;
+    let mut a_terms: Vec<(usize, usize, [u8; 32])> = Vec::new();
+    let mut b_terms: Vec<(usize, usize, [u8; 32])> = Vec::new();
+    let mut c_terms: Vec<(usize, usize, [u8; 32])> = Vec::new();
+
+    for _ in 0..num_sections {
+        let sec_type = reader.read_u32::<LittleEndian>()?;
+        let sec_size = reader.read_u64::<LittleEndian>()?;
+
+        if sec_type == R1CS_HEADER_SECTION {
+            header = Some(read_r1cs_header(&mut reader)?);
+        } else if sec_type == R1CS_CONSTRAINTS_SECTION {
+            let header = header.as_ref().ok_or(SpartanError::InvalidHeader)?;
+            for con in 0..header.num_constraints as usize {
+                let a = read_lc(&mut reader, header.field_size)?;
+                let b = read_lc(&mut reader, header.field_size)?;
+                let c = read_lc(&mut reader, header.field_size)?;
+
+                let num_inputs = (header.num_pub_out + header.num_pub_in) as usize;
+                let num_vars = header.num_wires as usize - 1 - num_inputs;
+
+                for (wire_id, coeff) in a {
+                    a_terms.push((con, r1cs_wire_to_col(wire_id, num_vars, num_inputs), coeff));
+                }
+                for (wire_id, coeff) in b {
+                    b_terms.push((con, r1cs_wire_to_col(wire_id, num_vars, num_inputs), coeff));
+                }
+                for (wire_id, coeff) in c {
+                    c_terms.push((con, r1cs_wire_to_col(wire_id, num_vars, num_inputs), coeff));
+                }
+            }
+        } else {
+            // unknown section, skip it
+            let mut skip = vec![0u8; sec_size as usize];
+            reader.read_exact(&mut skip)?;
+        }
+    }
+
+    let header = header.ok_or(SpartanError::InvalidHeader)?;
+    let num_inputs = (header.num_pub_out + header.num_pub_in) as usize;
+    let num_vars = header.num_wires as usize - 1 - num_inputs;
+    let num_cons = header.num_constraints as usize;
+
+    Instance::new(num_cons, num_vars, num_inputs, &a_terms, &b_terms, &c_terms)
+        .map_err(|e| SpartanError::FieldDecode(format!("invalid r1cs instance: {:?}", e)))
+}
+
+#[wasm_bindgen]
+pub fn prove_from_r1cs(
+    r1cs: &[u8],
+    vars: &[u8],
+    public_inputs: &[u8],
+    domain_label: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let circuit = load_r1cs_from_bin_reader(r1cs)?;
+    let circuit_bytes =
+        bincode::serialize(&circuit).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
+    prove(&circuit_bytes, vars, public_inputs, domain_label)
+}
+
+#[wasm_bindgen]
+pub fn verify_from_r1cs(
+    r1cs: &[u8],
+    proof: &[u8],
+    public_input: &[u8],
+    domain_label: &[u8],
+) -> Result<bool, JsValue> {
+    let circuit = load_r1cs_from_bin_reader(r1cs)?;
+    let circuit_bytes =
+        bincode::serialize(&circuit).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
+    verify(&circuit_bytes, proof, public_input, domain_label)
+}
+
+// `compress_point`/`decompress_point` round-trip a secq256k1 point through
+// its 33-byte compressed encoding (x-coordinate plus sign byte) via the
+// `Group`/`GroupEncoding` traits. `decompress_point` relies on
+// `GroupEncoding::from_bytes` to run the curve's on-curve/subgroup check and
+// additionally rejects the point-at-infinity, rather than silently
+// accepting it.
+//
+// `libspartan::NIZK` keeps its internal commitments private and doesn't hand
+// back `G1` values, so this crate has no way to decompose a proof into its
+// component points without a change upstream in `libspartan` itself (out of
+// scope here) — `prove_compact`/`verify_compact` below fall back to
+// re-encoding the whole proof through `postcard` for a smaller, version-
+// stable wire format instead. `compress_point`/`decompress_point` are real,
+// externally-callable API (`compress_pubkey`/`decompress_pubkey` below), not
+// just test fixtures: a caller preparing the public-key point that's part of
+// this circuit's public input can validate and compress it with them before
+// ever touching `prove`/`prove_compact`.
+const COMPRESSED_POINT_LEN: usize = 33;
+
+pub fn compress_point(point: &G1) -> [u8; COMPRESSED_POINT_LEN] {
+    let mut out = [0u8; COMPRESSED_POINT_LEN];
+    out.copy_from_slice(point.to_bytes().as_ref());
+    out
+}
+
+pub fn decompress_point(bytes: &[u8; COMPRESSED_POINT_LEN]) -> Result<G1, SpartanError> {
+    let mut repr = <G1 as GroupEncoding>::Repr::default();
+    repr.as_mut().copy_from_slice(bytes);
+    let point: G1 = Option::from(G1::from_bytes(&repr)).ok_or_else(|| {
+        SpartanError::FieldDecode("point is not a valid secq256k1 encoding".into())
+    })?;
+    if bool::from(point.is_identity()) {
+        return Err(SpartanError::FieldDecode(
+            "point at infinity is not a valid proof element".into(),
+        ));
+    }
+    Ok(point)
+}
+
+/// Validates that `bytes` is a well-formed, non-identity secq256k1 point and
+/// returns its canonical 33-byte compressed encoding, so JS callers can
+/// normalize/validate a public-key point before folding it into
+/// `public_inputs`.
+#[wasm_bindgen]
+pub fn compress_pubkey(bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let bytes: &[u8; COMPRESSED_POINT_LEN] = bytes
+        .try_into()
+        .map_err(|_| SpartanError::InvalidInput("expected a 33-byte compressed point".into()))?;
+    let point = decompress_point(bytes)?;
+    Ok(compress_point(&point).to_vec())
+}
+
+/// Runs the same check as `compress_pubkey`: validates a compressed point and
+/// returns it unchanged, rejecting anything that isn't a valid on-curve,
+/// in-subgroup, non-identity secq256k1 point. It returns the 33-byte
+/// compressed encoding, not an uncompressed one.
+#[wasm_bindgen]
+pub fn decompress_pubkey(bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
+    compress_pubkey(bytes)
+}
+
+// NOTE: this is a reduced-scope stand-in for the compact codec the original
+// request asked for (each proof element as a 33-byte compressed point /
+// 32-byte LE scalar, reconstructed with an explicit on-curve/subgroup and
+// infinity check on read). `libspartan::NIZK` doesn't expose its commitments
+// as `G1`/scalar values — see the comment above `compress_point` — so that
+// codec can't be built from this crate alone; it needs accessors added
+// upstream in `libspartan` first. Until then, `prove_compact`/`verify_compact`
+// re-encode the whole `NIZK` through `postcard` instead of `bincode`: postcard
+// writes integers and sequence lengths as varints rather than bincode's
+// fixed 8-byte length prefixes, and (unlike bincode, which documents its wire
+// format as an implementation detail) commits to a stable, documented
+// encoding across versions. This shrinks and stabilizes the proof but does
+// not touch individual points, so it does not get the requested on-curve/
+// infinity check on proof elements or the "half the size" target. No public
+// inputs are embedded in the envelope — the caller already supplies them to
+// `verify`/`verify_compact` directly, so duplicating them here would only
+// grow the payload.
+#[wasm_bindgen]
+pub fn prove_compact(
+    circuit: &[u8],
+    vars: &[u8],
+    public_inputs: &[u8],
+    domain_label: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let proof_bytes = prove(circuit, vars, public_inputs, domain_label)?;
+    let proof: NIZK =
+        bincode::deserialize(&proof_bytes).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
+
+    postcard::to_allocvec(&proof).map_err(|e| SpartanError::Deserialize(e.to_string()).into())
+}
+
+#[wasm_bindgen]
+pub fn verify_compact(
+    circuit: &[u8],
+    compact_proof: &[u8],
+    public_input: &[u8],
+    domain_label: &[u8],
+) -> Result<bool, JsValue> {
+    let proof: NIZK = postcard::from_bytes(compact_proof)
+        .map_err(|e| SpartanError::Deserialize(e.to_string()))?;
+    let proof_bytes =
+        bincode::serialize(&proof).map_err(|e| SpartanError::Deserialize(e.to_string()))?;
+
+    verify(circuit, &proof_bytes, public_input, domain_label)
+}
+
+#[cfg(test)]
+mod compact_codec_test {
+    use super::*;
+
+    #[test]
+    fn compress_point_round_trips_the_generator() {
+        let point = G1::generator();
+        let compressed = compress_point(&point);
+        assert_eq!(compressed.len(), COMPRESSED_POINT_LEN);
+
+        let decompressed = decompress_point(&compressed).unwrap();
+        assert_eq!(decompressed, point);
+    }
+
+    #[test]
+    fn decompress_point_rejects_the_point_at_infinity() {
+        let compressed = compress_point(&G1::identity());
+        let result = decompress_point(&compressed);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn decompress_point_rejects_malformed_bytes() {
+        let garbage = [0xffu8; COMPRESSED_POINT_LEN];
+        let result = decompress_point(&garbage);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn compress_pubkey_round_trips_a_valid_point() {
+        let point = G1::generator();
+        let compressed = compress_point(&point).to_vec();
+
+        let result = compress_pubkey(&compressed).unwrap();
+        assert_eq!(result, compressed);
+    }
+
+    #[test]
+    fn compress_pubkey_rejects_the_point_at_infinity() {
+        let compressed = compress_point(&G1::identity()).to_vec();
+        assert!(compress_pubkey(&compressed).is_err());
+    }
 }
 
 pub fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
     mut reader: R,
-) -> Result<Vec<Fr>, Error> {
+) -> Result<Vec<Fr>, SpartanError> {
     let mut wtns_header = [0u8; 4];
     reader.read_exact(&mut wtns_header)?;
     if wtns_header != [119, 116, 110, 115] {
         // ruby -e 'p "wtns".bytes' => [119, 116, 110, 115]
-        panic!("invalid file header");
+        return Err(SpartanError::InvalidHeader);
     }
     let version = reader.read_u32::<LittleEndian>()?;
-    // println!("wtns version {}", version);
     if version > 2 {
-        panic!("unsupported file version");
+        return Err(SpartanError::UnsupportedVersion(version));
     }
     let num_sections = reader.read_u32::<LittleEndian>()?;
     if num_sections != 2 {
-        panic!("invalid num sections");
+        return Err(SpartanError::InvalidHeader);
     }
     // read the first section
     let sec_type = reader.read_u32::<LittleEndian>()?;
     if sec_type != 1 {
-        panic!("invalid section type");
+        return Err(SpartanError::InvalidHeader);
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
     if sec_size != 4 + 32 + 4 {
-        panic!("invalid section len")
+        return Err(SpartanError::BadSectionSize {
+            expected: 4 + 32 + 4,
+            got: sec_size,
+        });
     }
     let field_size = reader.read_u32::<LittleEndian>()?;
     if field_size != 32 {
-        panic!("invalid field byte size");
+        return Err(SpartanError::FieldDecode(format!(
+            "unexpected field byte size {}",
+            field_size
+        )));
     }
     let mut prime = vec![0u8; field_size as usize];
     reader.read_exact(&mut prime)?;
-    // if prime != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
-    //     bail!("invalid curve prime {:?}", prime);
-    // }
+    if prime != SECQ256K1_FIELD_MODULUS {
+        return Err(SpartanError::FieldDecode(
+            "witness was generated for a different curve than secq256k1".into(),
+        ));
+    }
     let witness_len = reader.read_u32::<LittleEndian>()?;
-    // println!("witness len {}", witness_len);
     let sec_type = reader.read_u32::<LittleEndian>()?;
     if sec_type != 2 {
-        panic!("invalid section type");
+        return Err(SpartanError::InvalidHeader);
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
     if sec_size != (witness_len * field_size) as u64 {
-        panic!("invalid witness section size {}", sec_size);
+        return Err(SpartanError::BadSectionSize {
+            expected: (witness_len * field_size) as u64,
+            got: sec_size,
+        });
     }
     let mut result = Vec::with_capacity(witness_len as usize);
     for _ in 0..witness_len {
@@ -166,6 +566,7 @@ mod test {
             circuit.as_slice(),
             vars.as_slice(),
             public_inputs.as_slice(),
+            b"check_nizk",
         )
         .unwrap();
 
@@ -173,8 +574,207 @@ mod test {
             circuit.as_slice(),
             proof.as_slice(),
             public_inputs.as_slice(),
+            b"check_nizk",
         );
 
         assert!(result.unwrap());
     }
 }
+
+// Fixture-free coverage for the r1cs loader, the multi-input path, domain
+// separation, and the compact codec: `check_nizk` above depends on
+// `test_circuit/` fixture files this crate doesn't vendor, so none of these
+// exercise that directory. Instead they build a tiny two-constraint circuit
+// by hand — `vars[i] * 1 = inputs[i]` for `i` in `0..2` — with two unused
+// padding variables so `num_vars` stays a power of two above `num_inputs`,
+// as `libspartan::Instance` requires.
+#[cfg(test)]
+mod fixture_free_test {
+    use super::*;
+
+    const NUM_VARS: usize = 4;
+    const NUM_INPUTS: usize = 2;
+    const NUM_CONS: usize = 2;
+
+    fn one_repr() -> [u8; 32] {
+        let mut repr = [0u8; 32];
+        repr[0] = 1;
+        repr
+    }
+
+    // `vars[i] * 1 = inputs[i]` for i in 0..NUM_INPUTS, using the same
+    // z = (vars, 1, inputs) column convention as `r1cs_wire_to_col`.
+    fn minimal_instance() -> Instance {
+        let mut a_terms = Vec::new();
+        let mut b_terms = Vec::new();
+        let mut c_terms = Vec::new();
+        for i in 0..NUM_INPUTS {
+            a_terms.push((i, i, one_repr()));
+            b_terms.push((i, NUM_VARS, one_repr()));
+            c_terms.push((i, NUM_VARS + 1 + i, one_repr()));
+        }
+        Instance::new(NUM_CONS, NUM_VARS, NUM_INPUTS, &a_terms, &b_terms, &c_terms).unwrap()
+    }
+
+    // A full circom-style witness in wire-id order: the constant wire (1),
+    // then the public inputs, then the private vars — exactly what
+    // `load_witness_from_bin_reader` reads from a real `.wtns` file, and
+    // what `prove` must now strip the `1 + NUM_INPUTS`-element prefix from
+    // before handing the remainder to `Assignment::new`.
+    fn minimal_witness_bytes() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&F1::from(1u64).to_repr());
+        for i in 0..NUM_INPUTS {
+            bytes.extend_from_slice(&F1::from(7 + i as u64).to_repr());
+        }
+        for i in 0..NUM_VARS {
+            let value = if i < NUM_INPUTS { 7 + i as u64 } else { 0 };
+            bytes.extend_from_slice(&F1::from(value).to_repr());
+        }
+        build_wtns(&bytes)
+    }
+
+    fn minimal_public_input_bytes() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for i in 0..NUM_INPUTS {
+            bytes.extend_from_slice(&F1::from(7 + i as u64).to_repr());
+        }
+        bytes
+    }
+
+    // Wraps raw field-element bytes (one `F1::to_repr()` per variable) in a
+    // minimal valid circom `.wtns` file, mirroring the format
+    // `load_witness_from_bin_reader` parses.
+    fn build_wtns(field_elements: &[u8]) -> Vec<u8> {
+        let witness_len = (field_elements.len() / 32) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"wtns");
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&(4 + 32 + 4u64).to_le_bytes());
+        bytes.extend_from_slice(&32u32.to_le_bytes());
+        bytes.extend_from_slice(&SECQ256K1_FIELD_MODULUS);
+        bytes.extend_from_slice(&witness_len.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&((witness_len as u64) * 32).to_le_bytes());
+        bytes.extend_from_slice(field_elements);
+        bytes
+    }
+
+    // Wraps the same constraints `minimal_instance` builds directly into a
+    // raw circom `.r1cs` file, mirroring the format
+    // `load_r1cs_from_bin_reader` parses: wire 0 is the constant, wires
+    // `1..=NUM_INPUTS` are the public inputs, and the rest are private vars.
+    fn build_r1cs() -> Vec<u8> {
+        let num_wires = NUM_VARS + 1 + NUM_INPUTS;
+
+        let mut constraints = Vec::new();
+        for i in 0..NUM_INPUTS {
+            let var_wire = (1 + NUM_INPUTS + i) as u32;
+            let input_wire = (1 + i) as u32;
+            for lc in [
+                vec![(var_wire, one_repr())],
+                vec![(0u32, one_repr())],
+                vec![(input_wire, one_repr())],
+            ] {
+                constraints.extend_from_slice(&(lc.len() as u32).to_le_bytes());
+                for (wire_id, coeff) in lc {
+                    constraints.extend_from_slice(&wire_id.to_le_bytes());
+                    constraints.extend_from_slice(&coeff);
+                }
+            }
+        }
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&32u32.to_le_bytes());
+        header.extend_from_slice(&[0u8; 32]);
+        header.extend_from_slice(&(num_wires as u32).to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes()); // num_pub_out
+        header.extend_from_slice(&(NUM_INPUTS as u32).to_le_bytes()); // num_pub_in
+        header.extend_from_slice(&0u32.to_le_bytes()); // num_prv_in
+        header.extend_from_slice(&0u64.to_le_bytes()); // num_labels
+        header.extend_from_slice(&(NUM_CONS as u32).to_le_bytes());
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[0x72, 0x31, 0x63, 0x73]);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+
+        bytes.extend_from_slice(&R1CS_HEADER_SECTION.to_le_bytes());
+        bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&header);
+
+        bytes.extend_from_slice(&R1CS_CONSTRAINTS_SECTION.to_le_bytes());
+        bytes.extend_from_slice(&(constraints.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&constraints);
+
+        bytes
+    }
+
+    // chunk0-1: the r1cs loader builds an `Instance` that actually proves
+    // and verifies against a real circom witness layout (constant wire,
+    // then public inputs, then private vars — not a bare vars-only buffer),
+    // not just one that parses without error.
+    #[test]
+    fn load_r1cs_from_bin_reader_produces_a_satisfiable_instance() {
+        let r1cs = build_r1cs();
+        let vars = minimal_witness_bytes();
+        let public_inputs = minimal_public_input_bytes();
+
+        let proof = prove_from_r1cs(&r1cs, &vars, &public_inputs, b"fixture_free").unwrap();
+        let result = verify_from_r1cs(&r1cs, &proof, &public_inputs, b"fixture_free");
+
+        assert!(result.unwrap());
+    }
+
+    // chunk0-3: a circuit with more than one public input proves and
+    // verifies, and a tampered second input is rejected.
+    #[test]
+    fn multi_input_proof_round_trips_and_rejects_a_tampered_input() {
+        let circuit = bincode::serialize(&minimal_instance()).unwrap();
+        let vars = minimal_witness_bytes();
+        let public_inputs = minimal_public_input_bytes();
+
+        let proof = prove(&circuit, &vars, &public_inputs, b"fixture_free").unwrap();
+        assert!(verify(&circuit, &proof, &public_inputs, b"fixture_free").unwrap());
+
+        let mut tampered = public_inputs.clone();
+        tampered[32] ^= 0xff;
+        assert!(!verify(&circuit, &proof, &tampered, b"fixture_free").unwrap());
+    }
+
+    // chunk0-5: a proof bound to one domain label doesn't verify under a
+    // different one, even against the same circuit and public input.
+    #[test]
+    fn verify_rejects_a_mismatched_domain_label() {
+        let circuit = bincode::serialize(&minimal_instance()).unwrap();
+        let vars = minimal_witness_bytes();
+        let public_inputs = minimal_public_input_bytes();
+
+        let proof = prove(&circuit, &vars, &public_inputs, b"domain_a").unwrap();
+        let result = verify(&circuit, &proof, &public_inputs, b"domain_b");
+
+        assert!(!result.unwrap());
+    }
+
+    // chunk0-2: `prove_compact`/`verify_compact` round-trip a proof through
+    // the postcard re-encoding, and `verify_compact` rejects a tampered
+    // compact proof.
+    #[test]
+    fn compact_proof_round_trips_and_rejects_tampering() {
+        let circuit = bincode::serialize(&minimal_instance()).unwrap();
+        let vars = minimal_witness_bytes();
+        let public_inputs = minimal_public_input_bytes();
+
+        let compact_proof =
+            prove_compact(&circuit, &vars, &public_inputs, b"fixture_free").unwrap();
+        assert!(verify_compact(&circuit, &compact_proof, &public_inputs, b"fixture_free").unwrap());
+
+        let mut tampered = compact_proof.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+        let result = verify_compact(&circuit, &tampered, &public_inputs, b"fixture_free");
+        assert!(result.is_err() || !result.unwrap());
+    }
+}
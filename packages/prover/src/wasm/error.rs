@@ -0,0 +1,59 @@
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+/// Errors surfaced by the witness/circuit loaders and the `prove`/`verify`
+/// entry points. Every variant carries enough context to build a
+/// descriptive `JsValue`, so a malformed input surfaces as a recoverable
+/// JS error instead of aborting the whole WASM instance.
+#[derive(Debug)]
+pub enum SpartanError {
+    /// The file's magic bytes, section count or a section type isn't what the
+    /// format expects, or a required header section is missing.
+    InvalidHeader,
+    /// The file declares a format version this loader doesn't support.
+    UnsupportedVersion(u32),
+    /// A section's declared byte length doesn't match what the format requires.
+    BadSectionSize { expected: u64, got: u64 },
+    /// A field element (curve prime, coefficient, witness value, ...) failed to decode.
+    FieldDecode(String),
+    /// `bincode` or `postcard` failed to (de)serialize a circuit or proof.
+    Deserialize(String),
+    /// A caller-supplied argument (witness length, public input length, point length, ...) was invalid.
+    InvalidInput(String),
+    /// The underlying reader failed (truncated file, I/O error, ...).
+    Io(std::io::Error),
+}
+
+impl fmt::Display for SpartanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpartanError::InvalidHeader => write!(f, "invalid file header"),
+            SpartanError::UnsupportedVersion(version) => {
+                write!(f, "unsupported file version {}", version)
+            }
+            SpartanError::BadSectionSize { expected, got } => write!(
+                f,
+                "invalid section size: expected {} bytes, got {}",
+                expected, got
+            ),
+            SpartanError::FieldDecode(msg) => write!(f, "failed to decode field element: {}", msg),
+            SpartanError::Deserialize(msg) => write!(f, "failed to (de)serialize: {}", msg),
+            SpartanError::InvalidInput(msg) => write!(f, "{}", msg),
+            SpartanError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SpartanError {}
+
+impl From<std::io::Error> for SpartanError {
+    fn from(err: std::io::Error) -> Self {
+        SpartanError::Io(err)
+    }
+}
+
+impl From<SpartanError> for JsValue {
+    fn from(err: SpartanError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}